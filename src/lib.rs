@@ -1,9 +1,10 @@
 use anyhow::Result;
-use futures_util::Stream;
+use futures_util::{Stream, StreamExt};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::pin::Pin;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
 /// Status events that backends can emit during processing
@@ -13,10 +14,12 @@ pub enum BackendStatus {
     RetryAttempt {
         attempt: usize,
         max_attempts: usize,
+        /// The final delay after jitter and clamping, in milliseconds.
         delay_ms: u64,
         reason: String,
     },
-    /// Request is being rate limited
+    /// Request is being rate limited. `delay_ms` is the effective delay,
+    /// whether driven by the provider's retry-after or a shared freeze.
     RateLimited {
         attempt: usize,
         max_attempts: usize,
@@ -26,6 +29,11 @@ pub enum BackendStatus {
     NonRetryableError {
         message: String,
     },
+    /// A retry was denied because the shared retry token bucket is empty
+    RetryBudgetExhausted {
+        attempt: usize,
+        max_attempts: usize,
+    },
 }
 
 /// Callback function type for status updates
@@ -40,12 +48,142 @@ pub trait LLMBackend: Send + Sync {
     /// Send a chat request and get a streaming response
     async fn chat_stream(&self, request: ChatRequest) -> Result<ChatStream>;
 
-    /// Send a chat request with custom retry configuration
+    /// Send a chat request, retrying failed attempts and, if `should_retry`
+    /// is set, retries on an unwanted *successful* response too (e.g. an
+    /// empty tool call when one was expected).
+    ///
+    /// Retries after an error share their retry decisions with
+    /// `chat_stream_with_retry`: the same backoff/jitter, token bucket, and
+    /// cross-request freeze apply here. Retries of a rejected successful
+    /// response use only `max_retries` and the backoff/jitter schedule; they
+    /// neither charge the token bucket nor wait on the freeze.
     async fn chat_with_retry(
         &self,
         request: ChatRequest,
         retry_config: RetryConfig,
-    ) -> Result<ChatResponse>;
+    ) -> Result<ChatResponse> {
+        let mut attempt = 0usize;
+
+        loop {
+            match self.chat(request.clone()).await {
+                Ok(response) => {
+                    let retry_success = retry_config
+                        .should_retry
+                        .as_ref()
+                        .is_some_and(|should_retry| should_retry(&RetryOutcome::Ok(&response)));
+
+                    if !retry_success {
+                        refill_on_success(&retry_config);
+                        return Ok(response);
+                    }
+
+                    attempt += 1;
+                    match next_retry_delay_for_unwanted_success(&retry_config, &request, attempt) {
+                        Some(delay) => tokio::time::sleep(delay).await,
+                        None => return Ok(response),
+                    }
+                }
+                Err(err) => {
+                    attempt += 1;
+                    match next_retry_delay(&retry_config, &request, attempt, &err).await {
+                        Some(delay) => tokio::time::sleep(delay).await,
+                        None => return Err(err),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Stream a chat response with automatic reconnect-and-resume.
+    ///
+    /// On a retryable error event before `ChatStreamEvent::End`, the
+    /// underlying stream is transparently re-established via `chat_stream`
+    /// using `retry_config`'s backoff/jitter (honoring its token bucket,
+    /// cross-request freeze, and a provider's retry-after, exactly like
+    /// `chat_with_retry`), carrying forward the partial assistant text
+    /// accumulated so far as context so generation can continue. Reconnects
+    /// are coalesced: callers only ever see a single `Start` event. Gives up
+    /// with a terminal error once `max_retries` is exhausted, the retry
+    /// policy declines, or the token bucket is empty.
+    ///
+    /// Takes `self: Arc<Self>` rather than `&self` because the reconnect
+    /// loop is a `'static` stream that must be able to call back into
+    /// `chat_stream` after this method returns; a borrowed `&self` can't
+    /// satisfy that.
+    async fn chat_stream_with_retry(
+        self: Arc<Self>,
+        request: ChatRequest,
+        retry_config: RetryConfig,
+    ) -> Result<ChatStream>
+    where
+        Self: Sized + 'static,
+    {
+        let stream = async_stream::try_stream! {
+            let mut attempt = 0usize;
+            let mut partial_text = String::new();
+            let mut started = false;
+            let mut current_request = request.clone();
+
+            'reconnect: loop {
+                let mut inner = match self.chat_stream(current_request.clone()).await {
+                    Ok(inner) => inner,
+                    Err(err) => {
+                        attempt += 1;
+                        match next_retry_delay(&retry_config, &request, attempt, &err).await {
+                            Some(delay) => {
+                                tokio::time::sleep(delay).await;
+                                current_request = resume_request(&request, &partial_text);
+                                continue 'reconnect;
+                            }
+                            None => {
+                                Err(err)?;
+                                return;
+                            }
+                        }
+                    }
+                };
+
+                while let Some(event) = inner.next().await {
+                    match event {
+                        Ok(ChatStreamEvent::Start { role }) => {
+                            if !started {
+                                started = true;
+                                yield ChatStreamEvent::Start { role };
+                            }
+                        }
+                        Ok(ChatStreamEvent::TextDelta { text }) => {
+                            partial_text.push_str(&text);
+                            yield ChatStreamEvent::TextDelta { text };
+                        }
+                        Ok(ChatStreamEvent::End { usage }) => {
+                            refill_on_success(&retry_config);
+                            yield ChatStreamEvent::End { usage };
+                            return;
+                        }
+                        Ok(other) => yield other,
+                        Err(err) => {
+                            attempt += 1;
+                            match next_retry_delay(&retry_config, &request, attempt, &err).await {
+                                Some(delay) => {
+                                    tokio::time::sleep(delay).await;
+                                    current_request = resume_request(&request, &partial_text);
+                                    continue 'reconnect;
+                                }
+                                None => {
+                                    Err(err)?;
+                                    return;
+                                }
+                            }
+                        }
+                    }
+                }
+
+                return;
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
 
     /// Check if this backend supports tool calling
     fn supports_tools(&self) -> bool;
@@ -58,25 +196,70 @@ pub trait LLMBackend: Send + Sync {
 }
 
 /// Configuration for retry behavior
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct RetryConfig {
     pub max_retries: usize,
     pub initial_delay: Duration,
     pub backoff_strategy: BackoffStrategy,
+    /// Upper bound applied to every computed delay, after jitter.
+    pub max_delay: Duration,
+    /// Jitter applied to the backoff delay to avoid thundering-herd retries.
+    pub jitter: Jitter,
+    /// Shared token bucket capping retry amplification across concurrent
+    /// requests made through the same backend instance.
+    pub token_bucket: Option<Arc<RetryTokenBucket>>,
+    /// Shared cross-request freeze so concurrent requests on the same
+    /// backend pause together after a provider rate limit, instead of
+    /// each retrying independently.
+    pub freeze: Option<RetryFreeze>,
+    /// Caller-supplied policy overriding what counts as retryable, for both
+    /// errors and otherwise-successful responses. Falls back to
+    /// `BackendError::is_retryable()` when unset.
+    pub should_retry: Option<ShouldRetryFn>,
     pub verbose: bool,
 }
 
+impl std::fmt::Debug for RetryConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryConfig")
+            .field("max_retries", &self.max_retries)
+            .field("initial_delay", &self.initial_delay)
+            .field("backoff_strategy", &self.backoff_strategy)
+            .field("max_delay", &self.max_delay)
+            .field("jitter", &self.jitter)
+            .field("token_bucket", &self.token_bucket)
+            .field("freeze", &self.freeze)
+            .field("should_retry", &self.should_retry.as_ref().map(|_| "<predicate>"))
+            .field("verbose", &self.verbose)
+            .finish()
+    }
+}
+
 impl Default for RetryConfig {
     fn default() -> Self {
         Self {
             max_retries: 10,
             initial_delay: Duration::from_millis(2000),
             backoff_strategy: BackoffStrategy::Exponential { multiplier: 3 },
+            max_delay: Duration::from_secs(60),
+            jitter: Jitter::Full,
+            token_bucket: None,
+            freeze: None,
+            should_retry: None,
             verbose: false,
         }
     }
 }
 
+impl RetryConfig {
+    /// Compute the delay to wait before the given retry attempt (1-indexed),
+    /// applying jitter and clamping to `max_delay`.
+    pub fn compute_delay(&self, attempt: usize) -> Duration {
+        let base = self.backoff_strategy.base_delay(self.initial_delay, attempt);
+        self.jitter.apply(base).min(self.max_delay)
+    }
+}
+
 /// Backoff strategy for retries
 #[derive(Clone, Debug)]
 pub enum BackoffStrategy {
@@ -88,6 +271,144 @@ pub enum BackoffStrategy {
     Linear { increment: Duration },
 }
 
+impl BackoffStrategy {
+    /// Compute the base delay (before jitter) for the given retry attempt (1-indexed).
+    pub fn base_delay(&self, initial_delay: Duration, attempt: usize) -> Duration {
+        match self {
+            BackoffStrategy::Fixed => initial_delay,
+            BackoffStrategy::Exponential { multiplier } => {
+                initial_delay.saturating_mul(multiplier.saturating_pow(attempt as u32))
+            }
+            BackoffStrategy::Linear { increment } => {
+                initial_delay + increment.saturating_mul(attempt as u32)
+            }
+        }
+    }
+}
+
+/// Jitter applied to a computed backoff delay to prevent many clients from
+/// retrying in lockstep against the same provider.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum Jitter {
+    /// No jitter; use the base delay as-is.
+    #[default]
+    None,
+    /// Pick a uniform random delay in `[0, base]`.
+    Full,
+    /// Pick a uniform random delay in `[base / 2, base]`.
+    Equal,
+}
+
+impl Jitter {
+    /// Apply this jitter strategy to a base delay.
+    pub fn apply(&self, base: Duration) -> Duration {
+        match self {
+            Jitter::None => base,
+            Jitter::Full => Duration::from_millis(rand::thread_rng().gen_range(0..=base.as_millis() as u64)),
+            Jitter::Equal => {
+                let half_ms = base.as_millis() as u64 / 2;
+                Duration::from_millis(half_ms + rand::thread_rng().gen_range(0..=half_ms))
+            }
+        }
+    }
+}
+
+/// A shared token bucket that caps retry amplification against a provider
+/// when a burst of requests is failing at once.
+///
+/// Every retry after a failed attempt (a request's first attempt is free)
+/// must acquire tokens before sleeping and retrying; retries of a successful
+/// response rejected by `should_retry` are not charged. Once the bucket is empty, the retry
+/// loop gives up immediately instead of piling more load on a degraded
+/// provider. Successful attempts trickle tokens back in.
+#[derive(Debug)]
+pub struct RetryTokenBucket {
+    capacity: u32,
+    tokens: Mutex<u32>,
+    /// Tokens charged for a retry triggered by a timeout/network error.
+    pub timeout_cost: u32,
+    /// Tokens charged for a retry triggered by provider throttling.
+    pub throttle_cost: u32,
+    /// Tokens restored to the bucket after a successful attempt.
+    pub success_refill: u32,
+}
+
+impl RetryTokenBucket {
+    pub fn new(capacity: u32, timeout_cost: u32, throttle_cost: u32, success_refill: u32) -> Self {
+        Self {
+            capacity,
+            tokens: Mutex::new(capacity),
+            timeout_cost,
+            throttle_cost,
+            success_refill,
+        }
+    }
+
+    /// Try to acquire `cost` tokens, returning `false` if the bucket doesn't have enough.
+    pub fn try_acquire(&self, cost: u32) -> bool {
+        let mut tokens = self.tokens.lock().unwrap();
+        if *tokens >= cost {
+            *tokens -= cost;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Refill the bucket by `amount`, capped at capacity.
+    pub fn refill(&self, amount: u32) {
+        let mut tokens = self.tokens.lock().unwrap();
+        *tokens = (*tokens + amount).min(self.capacity);
+    }
+
+    /// Tokens currently available.
+    pub fn available(&self) -> u32 {
+        *self.tokens.lock().unwrap()
+    }
+}
+
+impl Default for RetryTokenBucket {
+    fn default() -> Self {
+        Self::new(500, 20, 5, 2)
+    }
+}
+
+/// A shared, cross-request rate-limit freeze for a single backend instance.
+///
+/// When one request observes a rate limit with a retry-after window, it
+/// records the wake-at instant here. Other concurrent requests on the same
+/// backend check this before issuing their next call so they pause until
+/// the window lifts, rather than all retrying simultaneously.
+#[derive(Clone, Debug, Default)]
+pub struct RetryFreeze {
+    wake_at: Arc<Mutex<Option<Instant>>>,
+}
+
+impl RetryFreeze {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Extend the freeze to last at least `retry_after` from now.
+    pub fn freeze_for(&self, retry_after: Duration) {
+        let wake_at = Instant::now() + retry_after;
+        let mut guard = self.wake_at.lock().unwrap();
+        let should_extend = match *guard {
+            Some(current) => wake_at > current,
+            None => true,
+        };
+        if should_extend {
+            *guard = Some(wake_at);
+        }
+    }
+
+    /// Time remaining before the freeze lifts, or `None` if not frozen.
+    pub fn remaining(&self) -> Option<Duration> {
+        let guard = self.wake_at.lock().unwrap();
+        guard.and_then(|wake_at| wake_at.checked_duration_since(Instant::now()))
+    }
+}
+
 /// A chat request to send to an LLM backend
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ChatRequest {
@@ -113,6 +434,165 @@ impl std::fmt::Debug for ChatRequest {
     }
 }
 
+/// Build a follow-up request that continues generation after a stream
+/// reconnect, carrying forward the partial assistant text seen before the drop.
+pub fn resume_request(original: &ChatRequest, partial_text: &str) -> ChatRequest {
+    if partial_text.is_empty() {
+        return original.clone();
+    }
+
+    let mut messages = original.messages.clone();
+    messages.push(Message::text(MessageRole::Assistant, partial_text));
+    ChatRequest {
+        messages,
+        ..original.clone()
+    }
+}
+
+/// Whether a failed attempt should be retried, per `retry_config.should_retry`
+/// (falling back to `BackendError::is_retryable()`). Errors that don't
+/// downcast to `BackendError` are treated as non-retryable.
+pub fn retry_should_continue(retry_config: &RetryConfig, err: &anyhow::Error) -> bool {
+    match err.downcast_ref::<BackendError>() {
+        Some(backend_err) => match &retry_config.should_retry {
+            Some(should_retry) => should_retry(&RetryOutcome::Err(backend_err)),
+            None => backend_err.is_retryable(),
+        },
+        None => false,
+    }
+}
+
+/// Tokens to charge the retry token bucket for this error: the cheaper
+/// throttle cost for a rate limit, the timeout cost for everything else.
+pub fn token_cost_for(bucket: &RetryTokenBucket, err: &anyhow::Error) -> u32 {
+    match err.downcast_ref::<BackendError>() {
+        Some(BackendError::RateLimited { .. }) => bucket.throttle_cost,
+        _ => bucket.timeout_cost,
+    }
+}
+
+/// The provider's retry-after window, if `err` is a `BackendError::RateLimited` that carries one.
+pub fn rate_limit_retry_after(err: &anyhow::Error) -> Option<Duration> {
+    match err.downcast_ref::<BackendError>() {
+        Some(BackendError::RateLimited { retry_after }) => *retry_after,
+        _ => None,
+    }
+}
+
+/// The delay to use for this retry: the provider's retry-after window when
+/// present, otherwise the backoff/jitter delay from `compute_delay`.
+pub fn retry_delay_for(retry_config: &RetryConfig, attempt: usize, err: &anyhow::Error) -> Duration {
+    match rate_limit_retry_after(err) {
+        Some(retry_after) => retry_after.min(retry_config.max_delay),
+        None => retry_config.compute_delay(attempt),
+    }
+}
+
+/// Decide whether a failed attempt should be retried, performing the
+/// token-bucket/freeze bookkeeping and emitting the matching `BackendStatus`
+/// along the way. Returns the delay to sleep before retrying, or `None` if
+/// the retry policy, retry budget, or token bucket says to give up.
+///
+/// An active freeze (from another concurrent request hitting a rate limit)
+/// replaces this attempt's own delay outright rather than being waited out
+/// in addition to it, so concurrent requests converge on one countdown
+/// instead of each stacking their own wait on top of it.
+pub async fn next_retry_delay(
+    retry_config: &RetryConfig,
+    request: &ChatRequest,
+    attempt: usize,
+    err: &anyhow::Error,
+) -> Option<Duration> {
+    if attempt > retry_config.max_retries || !retry_should_continue(retry_config, err) {
+        return None;
+    }
+
+    if let Some(bucket) = &retry_config.token_bucket {
+        if !bucket.try_acquire(token_cost_for(bucket, err)) {
+            if let Some(status_callback) = &request.status_callback {
+                status_callback(BackendStatus::RetryBudgetExhausted {
+                    attempt,
+                    max_attempts: retry_config.max_retries,
+                });
+            }
+            return None;
+        }
+    }
+
+    if let Some(freeze) = &retry_config.freeze {
+        if let Some(remaining) = freeze.remaining() {
+            if let Some(status_callback) = &request.status_callback {
+                status_callback(BackendStatus::RateLimited {
+                    attempt,
+                    max_attempts: retry_config.max_retries,
+                    delay_ms: remaining.as_millis() as u64,
+                });
+            }
+            return Some(remaining);
+        }
+    }
+
+    let delay = retry_delay_for(retry_config, attempt, err);
+
+    if let Some(retry_after) = rate_limit_retry_after(err) {
+        if let Some(freeze) = &retry_config.freeze {
+            freeze.freeze_for(retry_after);
+        }
+        if let Some(status_callback) = &request.status_callback {
+            status_callback(BackendStatus::RateLimited {
+                attempt,
+                max_attempts: retry_config.max_retries,
+                delay_ms: delay.as_millis() as u64,
+            });
+        }
+    } else if let Some(status_callback) = &request.status_callback {
+        status_callback(BackendStatus::RetryAttempt {
+            attempt,
+            max_attempts: retry_config.max_retries,
+            delay_ms: delay.as_millis() as u64,
+            reason: err.to_string(),
+        });
+    }
+
+    Some(delay)
+}
+
+/// Trickle tokens back into the retry token bucket after a successful
+/// attempt, if one is configured. A no-op otherwise.
+pub fn refill_on_success(retry_config: &RetryConfig) {
+    if let Some(bucket) = &retry_config.token_bucket {
+        bucket.refill(bucket.success_refill);
+    }
+}
+
+/// The delay before retrying a response that `should_retry` rejected despite
+/// the attempt itself succeeding (e.g. an empty tool call). There's no
+/// provider error to weigh here, so this only consults `max_retries` and the
+/// plain backoff/jitter schedule; the token bucket and freeze are for
+/// degraded-provider amplification, which doesn't apply to a validation
+/// retry against a provider that just answered fine.
+pub fn next_retry_delay_for_unwanted_success(
+    retry_config: &RetryConfig,
+    request: &ChatRequest,
+    attempt: usize,
+) -> Option<Duration> {
+    if attempt > retry_config.max_retries {
+        return None;
+    }
+
+    let delay = retry_config.compute_delay(attempt);
+    if let Some(status_callback) = &request.status_callback {
+        status_callback(BackendStatus::RetryAttempt {
+            attempt,
+            max_attempts: retry_config.max_retries,
+            delay_ms: delay.as_millis() as u64,
+            reason: "response did not pass should_retry validation".to_string(),
+        });
+    }
+
+    Some(delay)
+}
+
 /// A complete response from an LLM backend
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ChatResponse {
@@ -247,7 +727,10 @@ pub enum BackendError {
     UnsupportedModel { model: String },
 
     #[error("Rate limited by provider")]
-    RateLimited,
+    RateLimited {
+        /// Delay the provider asked callers to wait before retrying, if it sent one.
+        retry_after: Option<Duration>,
+    },
 
     #[error("Request validation failed: {message}")]
     ValidationError { message: String },
@@ -270,7 +753,7 @@ impl BackendError {
     pub fn is_retryable(&self) -> bool {
         matches!(
             self,
-            BackendError::RateLimited | BackendError::NetworkError { .. }
+            BackendError::RateLimited { .. } | BackendError::NetworkError { .. }
         )
     }
 }
@@ -278,9 +761,101 @@ impl BackendError {
 /// Result type alias for backend operations
 pub type BackendResult<T> = std::result::Result<T, BackendError>;
 
+/// The outcome of a single attempt, passed to a `should_retry` predicate so
+/// it can decide whether to retry an error *or* an unwanted success (e.g. an
+/// empty tool call when one was expected).
+#[derive(Debug)]
+pub enum RetryOutcome<'a> {
+    Err(&'a BackendError),
+    Ok(&'a ChatResponse),
+}
+
+/// A caller-supplied policy for whether an attempt's outcome should be retried.
+///
+/// Consulted in place of `BackendError::is_retryable()` when set, and also
+/// consulted on success so callers can retry validation failures that aren't
+/// backend errors at all.
+pub type ShouldRetryFn = Arc<dyn Fn(&RetryOutcome) -> bool + Send + Sync>;
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A backend whose `chat` keeps succeeding, used to exercise
+    /// `chat_with_retry`'s default impl without a real provider.
+    struct CountingBackend {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl LLMBackend for CountingBackend {
+        async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse> {
+            // Tag the response with the call index (as its session id) so
+            // the test can tell which attempt produced it.
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(ChatResponse {
+                message: Message::text(MessageRole::Assistant, "hi"),
+                tool_calls: vec![],
+                usage: None,
+                model: "test-model".to_string(),
+                session_id: Some(Uuid::from_u128(call as u128)),
+            })
+        }
+
+        async fn chat_stream(&self, _request: ChatRequest) -> Result<ChatStream> {
+            unimplemented!("not exercised by this test")
+        }
+
+        fn supports_tools(&self) -> bool {
+            false
+        }
+
+        fn supported_models(&self) -> Vec<String> {
+            vec!["test-model".to_string()]
+        }
+
+        fn default_model(&self) -> String {
+            "test-model".to_string()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_retry_retries_an_unwanted_successful_response() {
+        let backend = CountingBackend {
+            calls: AtomicUsize::new(0),
+        };
+        let bucket = Arc::new(RetryTokenBucket::new(10, 6, 3, 2));
+        bucket.try_acquire(6);
+        let retry_config = RetryConfig {
+            initial_delay: Duration::from_millis(1),
+            jitter: Jitter::None,
+            token_bucket: Some(bucket.clone()),
+            should_retry: Some(Arc::new(|outcome| match outcome {
+                // Reject the first attempt's response (session_id 0), accept
+                // everything after.
+                RetryOutcome::Ok(response) => response.session_id == Some(Uuid::from_u128(0)),
+                RetryOutcome::Err(_) => false,
+            })),
+            ..RetryConfig::default()
+        };
+        let request = ChatRequest {
+            messages: vec![],
+            model: None,
+            tools: None,
+            inference_config: None,
+            session_id: None,
+            status_callback: None,
+        };
+
+        let response = backend.chat_with_retry(request, retry_config).await.unwrap();
+
+        assert_eq!(response.session_id, Some(Uuid::from_u128(1)));
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 2);
+        // The final accepted attempt refills `success_refill` tokens on top
+        // of what was left after the earlier acquire.
+        assert_eq!(bucket.available(), 6);
+    }
 
     #[test]
     fn test_message_creation() {
@@ -295,4 +870,264 @@ mod tests {
         assert_eq!(config.max_retries, 10);
         assert_eq!(config.initial_delay, Duration::from_millis(2000));
     }
+
+    #[test]
+    fn test_jitter_full_bounds() {
+        let base = Duration::from_millis(1000);
+        for _ in 0..100 {
+            let delay = Jitter::Full.apply(base);
+            assert!(delay <= base);
+        }
+    }
+
+    #[test]
+    fn test_jitter_equal_bounds() {
+        let base = Duration::from_millis(1000);
+        for _ in 0..100 {
+            let delay = Jitter::Equal.apply(base);
+            assert!(delay >= Duration::from_millis(500) && delay <= base);
+        }
+    }
+
+    #[test]
+    fn test_compute_delay_clamped_to_max() {
+        let config = RetryConfig {
+            jitter: Jitter::None,
+            max_delay: Duration::from_millis(100),
+            ..RetryConfig::default()
+        };
+        assert_eq!(config.compute_delay(5), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_token_bucket_denies_when_empty() {
+        let bucket = RetryTokenBucket::new(10, 6, 3, 2);
+        assert!(bucket.try_acquire(6));
+        assert!(bucket.try_acquire(3));
+        assert!(!bucket.try_acquire(3));
+        assert_eq!(bucket.available(), 1);
+    }
+
+    #[test]
+    fn test_token_bucket_refill_caps_at_capacity() {
+        let bucket = RetryTokenBucket::new(10, 6, 3, 2);
+        bucket.try_acquire(6);
+        bucket.refill(100);
+        assert_eq!(bucket.available(), 10);
+    }
+
+    #[test]
+    fn test_freeze_remaining_none_when_not_frozen() {
+        let freeze = RetryFreeze::new();
+        assert!(freeze.remaining().is_none());
+    }
+
+    #[test]
+    fn test_freeze_keeps_longer_window() {
+        let freeze = RetryFreeze::new();
+        freeze.freeze_for(Duration::from_secs(10));
+        freeze.freeze_for(Duration::from_millis(1));
+        let remaining = freeze.remaining().expect("should still be frozen");
+        assert!(remaining > Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_should_retry_can_retry_a_successful_response() {
+        let should_retry: ShouldRetryFn = Arc::new(|outcome| match outcome {
+            RetryOutcome::Ok(response) => response.tool_calls.is_empty(),
+            RetryOutcome::Err(_) => false,
+        });
+
+        let response = ChatResponse {
+            message: Message::text(MessageRole::Assistant, "no tools called"),
+            tool_calls: vec![],
+            usage: None,
+            model: "test-model".to_string(),
+            session_id: None,
+        };
+
+        assert!(should_retry(&RetryOutcome::Ok(&response)));
+    }
+
+    #[test]
+    fn test_resume_request_appends_partial_text() {
+        let request = ChatRequest {
+            messages: vec![Message::text(MessageRole::User, "tell me a story")],
+            model: None,
+            tools: None,
+            inference_config: None,
+            session_id: None,
+            status_callback: None,
+        };
+
+        let resumed = resume_request(&request, "once upon a");
+        assert_eq!(resumed.messages.len(), 2);
+        assert_eq!(resumed.messages[1].role, MessageRole::Assistant);
+    }
+
+    #[test]
+    fn test_resume_request_noop_with_no_partial_text() {
+        let request = ChatRequest {
+            messages: vec![Message::text(MessageRole::User, "tell me a story")],
+            model: None,
+            tools: None,
+            inference_config: None,
+            session_id: None,
+            status_callback: None,
+        };
+
+        let resumed = resume_request(&request, "");
+        assert_eq!(resumed.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_retry_should_continue_defaults_to_is_retryable() {
+        let retry_config = RetryConfig::default();
+        let retryable = anyhow::Error::new(BackendError::NetworkError {
+            message: "reset".to_string(),
+        });
+        let not_retryable = anyhow::Error::new(BackendError::AuthenticationError);
+
+        assert!(retry_should_continue(&retry_config, &retryable));
+        assert!(!retry_should_continue(&retry_config, &not_retryable));
+    }
+
+    #[test]
+    fn test_retry_should_continue_respects_predicate() {
+        let retry_config = RetryConfig {
+            should_retry: Some(Arc::new(|outcome| matches!(
+                outcome,
+                RetryOutcome::Err(BackendError::AuthenticationError)
+            ))),
+            ..RetryConfig::default()
+        };
+        let err = anyhow::Error::new(BackendError::AuthenticationError);
+
+        assert!(retry_should_continue(&retry_config, &err));
+    }
+
+    #[tokio::test]
+    async fn test_next_retry_delay_gives_up_on_non_retryable_error() {
+        let retry_config = RetryConfig::default();
+        let request = ChatRequest {
+            messages: vec![],
+            model: None,
+            tools: None,
+            inference_config: None,
+            session_id: None,
+            status_callback: None,
+        };
+        let err = anyhow::Error::new(BackendError::AuthenticationError);
+
+        assert!(next_retry_delay(&retry_config, &request, 1, &err).await.is_none());
+    }
+
+    #[test]
+    fn test_token_cost_for_throttle_vs_timeout() {
+        let bucket = RetryTokenBucket::new(10, 6, 3, 2);
+        let rate_limited = anyhow::Error::new(BackendError::RateLimited { retry_after: None });
+        let network = anyhow::Error::new(BackendError::NetworkError {
+            message: "timeout".to_string(),
+        });
+
+        assert_eq!(token_cost_for(&bucket, &rate_limited), 3);
+        assert_eq!(token_cost_for(&bucket, &network), 6);
+    }
+
+    #[tokio::test]
+    async fn test_next_retry_delay_denied_when_bucket_empty() {
+        let statuses: Arc<Mutex<Vec<BackendStatus>>> = Arc::new(Mutex::new(Vec::new()));
+        let statuses_clone = statuses.clone();
+        let request = ChatRequest {
+            messages: vec![],
+            model: None,
+            tools: None,
+            inference_config: None,
+            session_id: None,
+            status_callback: Some(Arc::new(move |status| {
+                statuses_clone.lock().unwrap().push(status);
+            })),
+        };
+        let retry_config = RetryConfig {
+            token_bucket: Some(Arc::new(RetryTokenBucket::new(5, 6, 3, 2))),
+            ..RetryConfig::default()
+        };
+        let err = anyhow::Error::new(BackendError::NetworkError {
+            message: "timeout".to_string(),
+        });
+
+        let delay = next_retry_delay(&retry_config, &request, 1, &err).await;
+
+        assert!(delay.is_none());
+        assert!(matches!(
+            statuses.lock().unwrap().as_slice(),
+            [BackendStatus::RetryBudgetExhausted { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_refill_on_success_restores_tokens() {
+        let bucket = Arc::new(RetryTokenBucket::new(10, 6, 3, 2));
+        bucket.try_acquire(6);
+        let retry_config = RetryConfig {
+            token_bucket: Some(bucket.clone()),
+            ..RetryConfig::default()
+        };
+
+        refill_on_success(&retry_config);
+
+        assert_eq!(bucket.available(), 6);
+    }
+
+    #[test]
+    fn test_rate_limit_retry_after_extracted() {
+        let err = anyhow::Error::new(BackendError::RateLimited {
+            retry_after: Some(Duration::from_millis(250)),
+        });
+        assert_eq!(rate_limit_retry_after(&err), Some(Duration::from_millis(250)));
+
+        let other = anyhow::Error::new(BackendError::NetworkError {
+            message: "timeout".to_string(),
+        });
+        assert_eq!(rate_limit_retry_after(&other), None);
+    }
+
+    #[test]
+    fn test_retry_delay_for_prefers_retry_after() {
+        let retry_config = RetryConfig {
+            jitter: Jitter::None,
+            ..RetryConfig::default()
+        };
+        let err = anyhow::Error::new(BackendError::RateLimited {
+            retry_after: Some(Duration::from_millis(250)),
+        });
+
+        assert_eq!(retry_delay_for(&retry_config, 1, &err), Duration::from_millis(250));
+    }
+
+    #[tokio::test]
+    async fn test_next_retry_delay_extends_freeze_on_rate_limit() {
+        let retry_config = RetryConfig {
+            jitter: Jitter::None,
+            freeze: Some(RetryFreeze::new()),
+            ..RetryConfig::default()
+        };
+        let request = ChatRequest {
+            messages: vec![],
+            model: None,
+            tools: None,
+            inference_config: None,
+            session_id: None,
+            status_callback: None,
+        };
+        let err = anyhow::Error::new(BackendError::RateLimited {
+            retry_after: Some(Duration::from_secs(30)),
+        });
+
+        let delay = next_retry_delay(&retry_config, &request, 1, &err).await;
+
+        assert_eq!(delay, Some(Duration::from_secs(30)));
+        let remaining = retry_config.freeze.unwrap().remaining().expect("should be frozen");
+        assert!(remaining > Duration::from_secs(20));
+    }
 }